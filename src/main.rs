@@ -3,6 +3,7 @@
 extern crate serde;
 //
 use grok::{Grok, Pattern};
+use regex::Regex;
 use std::{
     collections::BTreeMap,
     convert::TryFrom,
@@ -19,6 +20,7 @@ pub enum Input {
     Paths {
         paths: Vec<path::PathBuf>,
         buffer: io::BufReader<fs::File>,
+        follow: bool,
     },
 }
 //
@@ -32,38 +34,153 @@ impl TryFrom<Vec<path::PathBuf>> for Input {
             Some(p) => Ok(Self::Paths {
                 paths,
                 buffer: io::BufReader::new(fs::OpenOptions::new().read(true).open(p)?),
+                follow: false,
             }),
         }
     }
 }
 //
+impl Input {
+    /// Switches file input into follow mode: keep polling for new data past EOF.
+    ///
+    /// With multiple input paths (e.g. from `--recursive` or a glob), only the last
+    /// file reached is ever polled this way — once an earlier file hits EOF, `next`
+    /// moves on to the next path for good and won't notice further appends to it.
+    pub fn following(self) -> Self {
+        match self {
+            Self::Paths { paths, buffer, .. } => Self::Paths {
+                paths,
+                buffer,
+                follow: true,
+            },
+            other => other,
+        }
+    }
+}
+//
 impl Iterator for Input {
-    type Item = Result<String>;
+    /// `Ok(Some(line))` is a record, `Ok(None)` an idle tick in `--follow` mode.
+    type Item = Result<Option<String>>;
     fn next(&mut self) -> Option<Self::Item> {
         match self {
-            Self::Stdin(stdin) => stdin.lock().lines().next().map(|r| r.map_err(|e| e.into())),
+            Self::Stdin(stdin) => stdin
+                .lock()
+                .lines()
+                .next()
+                .map(|r| r.map(Some).map_err(|e| e.into())),
             Self::Paths {
                 ref mut paths,
                 ref mut buffer,
-            } => {
+                follow,
+            } => loop {
                 // Try read from buffer
                 let mut line = String::new();
                 match buffer.read_line(&mut line) {
-                    Err(_) | Ok(0) => {
-                        match paths.pop() {
-                            Some(p) => {
-                                // Create a BufReader
-                                match fs::OpenOptions::new().read(true).open(p) {
-                                    Ok(f) => *buffer = io::BufReader::new(f),
-                                    Err(e) => return Some(Err(e.into())),
-                                }
-                                self.next()
+                    Ok(0) => match paths.pop() {
+                        Some(p) => {
+                            // Create a BufReader
+                            match fs::OpenOptions::new().read(true).open(p) {
+                                Ok(f) => *buffer = io::BufReader::new(f),
+                                Err(e) => return Some(Err(e.into())),
+                            }
+                            continue;
+                        }
+                        None if *follow => {
+                            // Temporarily no data: the file may still grow, so keep
+                            // the handle open and poll rather than ending the stream.
+                            // Return a single idle tick per poll instead of looping
+                            // here, so the caller isn't blocked for longer than one
+                            // tick during quiet periods.
+                            std::thread::sleep(std::time::Duration::from_millis(500));
+                            return Some(Ok(None));
+                        }
+                        None => return None,
+                    },
+                    Ok(_) => {
+                        // `read_line` keeps the trailing newline (unlike the `Stdin`
+                        // arm's `BufRead::lines`, which strips it), so trim it here to
+                        // give both sources the same line contract. Otherwise callers
+                        // that join lines back together (`MultilineJoiner`) would glue
+                        // the retained `\n` plus their own separator onto every line.
+                        if line.ends_with('\n') {
+                            line.pop();
+                            if line.ends_with('\r') {
+                                line.pop();
                             }
-                            None => None,
                         }
+                        return Some(Ok(Some(line)));
                     }
-                    Ok(_) => Some(Ok(line)),
+                    Err(e) => return Some(Err(e.into())),
+                }
+            },
+        }
+    }
+}
+//
+/// Glues continuation lines onto the previous record so multi-line records (stack
+/// traces, wrapped JSON) reach `GrokParser::parse` as a single logical line.
+pub struct MultilineJoiner<I> {
+    inner: I,
+    line_start: Option<Regex>,
+    buffered: Option<String>,
+    pending_err: Option<Box<dyn error::Error>>,
+}
+
+impl<I> MultilineJoiner<I> {
+    pub fn new(inner: I, line_start: Option<Regex>) -> Self {
+        Self {
+            inner,
+            line_start,
+            buffered: None,
+            pending_err: None,
+        }
+    }
+
+    fn is_continuation(&self, line: &str) -> bool {
+        match &self.line_start {
+            Some(re) => !re.is_match(line),
+            None => line.starts_with(' ') || line.starts_with('\t'),
+        }
+    }
+}
+
+impl<I: Iterator<Item = Result<Option<String>>>> Iterator for MultilineJoiner<I> {
+    type Item = Result<Option<String>>;
+    fn next(&mut self) -> Option<Self::Item> {
+        // A pending error from a previous call means the record in progress at the
+        // time was already flushed below; surface the error now instead of losing it.
+        if let Some(e) = self.pending_err.take() {
+            return Some(Err(e));
+        }
+        loop {
+            match self.inner.next() {
+                None => return self.buffered.take().map(|r| Ok(Some(r))),
+                Some(Err(e)) => {
+                    // Flush any record assembled so far before surfacing the error,
+                    // instead of dropping it: the error itself is queued and returned
+                    // on the next call.
+                    return match self.buffered.take() {
+                        Some(record) => {
+                            self.pending_err = Some(e);
+                            Some(Ok(Some(record)))
+                        }
+                        None => Some(Err(e)),
+                    };
                 }
+                Some(Ok(None)) => return Some(Ok(None)),
+                Some(Ok(Some(line))) => match self.buffered.take() {
+                    None => self.buffered = Some(line),
+                    Some(mut record) => {
+                        if self.is_continuation(&line) {
+                            record.push('\n');
+                            record.push_str(&line);
+                            self.buffered = Some(record);
+                        } else {
+                            self.buffered = Some(line);
+                            return Some(Ok(Some(record)));
+                        }
+                    }
+                },
             }
         }
     }
@@ -74,6 +191,7 @@ pub enum Output {
     File {
         path: path::PathBuf,
         err_path: path::PathBuf,
+        stats_path: path::PathBuf,
     },
 }
 //
@@ -84,6 +202,11 @@ impl TryFrom<Option<path::PathBuf>> for Output {
             None => Ok(Self::Print),
             Some(p) => {
                 let err_path = p.join(".err");
+                let stats_path = {
+                    let mut s = p.clone().into_os_string();
+                    s.push(".stats");
+                    path::PathBuf::from(s)
+                };
                 match (err_path.exists(), !p.exists()) {
                     (true, true) => Err(format!(
                         "Could not log to {} or {}. Both files already exist.",
@@ -99,7 +222,11 @@ impl TryFrom<Option<path::PathBuf>> for Output {
                         err_path.display()
                     )
                     .into()),
-                    (false, false) => Ok(Self::File { err_path, path: p }),
+                    (false, false) => Ok(Self::File {
+                        err_path,
+                        stats_path,
+                        path: p,
+                    }),
                 }
             }
         }
@@ -119,6 +246,7 @@ impl Output {
             Self::File {
                 ref path,
                 ref err_path,
+                ..
             } => match parse_res {
                 Ok(o) => {
                     let mut file = fs::OpenOptions::new()
@@ -140,16 +268,87 @@ impl Output {
             .map_err(|e| e.into()),
         }
     }
+    /// Rejects a leftover sibling `.stats` file before `--stats-interval` snapshots
+    /// start appending to it. Only called when `--stats-interval` is given,
+    /// so a stray `.stats` file from an earlier run doesn't hard-fail a plain run
+    /// against the same `-o` path.
+    pub fn ensure_stats_path_available(&self) -> Result<()> {
+        if let Self::File { ref stats_path, .. } = self {
+            if stats_path.exists() {
+                return Err(format!(
+                    "Could not log stats to {}, file already exists",
+                    stats_path.display()
+                )
+                .into());
+            }
+        }
+        Ok(())
+    }
+    /// Like `output`, but for `Stats` snapshots: writes to stderr (`Print`) or a
+    /// sibling `.stats` file (`File`) instead of the record stream, so a `-f csv`
+    /// run with `--stats-interval` doesn't interleave a different column schema
+    /// into the same file as the parsed records.
+    pub fn output_stats(&self, res: Result<String>) -> Result<()> {
+        match self {
+            Self::Print => {
+                match res {
+                    Ok(o) => eprintln!("{}", o),
+                    Err(e) => eprintln!("{}", e),
+                };
+                Ok(())
+            }
+            Self::File {
+                ref stats_path,
+                ref err_path,
+                ..
+            } => match res {
+                Ok(o) => {
+                    let mut file = fs::OpenOptions::new()
+                        .create(true)
+                        .append(true)
+                        .open(stats_path)?;
+                    let _ = file.write(o.as_bytes())?;
+                    file.write("\n".as_bytes())
+                }
+                Err(e) => {
+                    let mut file = fs::OpenOptions::new()
+                        .create(true)
+                        .append(true)
+                        .open(err_path)?;
+                    file.write(format!("{}\n", e.to_string()).as_bytes())
+                }
+            }
+            .map(|_| ())
+            .map_err(|e| e.into()),
+        }
+    }
 }
 //
+/// A single named grok pattern: one per `--rules` file, plus the `--pattern` fallback.
+#[derive(Debug)]
+pub struct Rule {
+    pub name: String,
+    pub pattern: Pattern,
+    /// Field name -> type suffix (`int`, `long`, `float`, `bool`), parsed out of the
+    /// raw pattern text before compiling it.
+    pub type_hints: BTreeMap<String, String>,
+}
+
 #[derive(Debug)]
 pub struct GrokParser {
     grok: Grok,
-    pattern: Pattern,
+    rules: Vec<Rule>,
 }
 
 impl GrokParser {
-    pub fn new(pattern: &str, patterns: Option<&path::PathBuf>, no_patterns: bool) -> Result<Self> {
+    /// Compiles every `--rules` file into a named rule, then appends `pattern` (if
+    /// given) as the final fallback rule, named `default`.
+    pub fn new(
+        pattern: Option<&str>,
+        rule_files: &[path::PathBuf],
+        patterns: Option<&path::PathBuf>,
+        no_patterns: bool,
+    ) -> Result<Self> {
         let mut grok = match patterns {
             Some(d) => {
                 //
@@ -165,26 +364,110 @@ impl GrokParser {
             None => Grok::with_patterns(),
         };
         //
-        let pattern = grok.compile(pattern, true)?;
+        let mut rules = Vec::new();
+        for (name, raw) in read_rules(rule_files)? {
+            let (raw, type_hints) = extract_type_hints(&raw);
+            let pattern = grok.compile(&raw, true)?;
+            rules.push(Rule { name, pattern, type_hints });
+        }
+        if let Some(p) = pattern {
+            let (p, type_hints) = extract_type_hints(p);
+            rules.push(Rule {
+                name: "default".to_string(),
+                pattern: grok.compile(&p, true)?,
+                type_hints,
+            });
+        }
+        if rules.is_empty() {
+            return Err("No --pattern or --rules provided to match against.".into());
+        }
         //
-        Ok(Self { grok, pattern })
+        Ok(Self { grok, rules })
     }
 
-    pub fn parse(&self, data: &str, stats: &mut Stats) -> Result<BTreeMap<String, String>> {
-        match self.pattern.match_against(data) {
-            None => {
-                stats.failed += 1;
-                Err(format!("No matches against data: \"{}\"", data.trim_end()).into())
-            }
-            Some(matches) => {
-                stats.parsed += 1;
+    /// Number of rules this parser will try, in order.
+    pub fn rule_count(&self) -> usize {
+        self.rules.len()
+    }
+
+    /// Tries each rule in order and returns the first match. When more than one rule
+    /// is in play, the match is tagged with `_rule` (unless the pattern itself already
+    /// captured a field of that name) so a downstream consumer can tell which rule
+    /// fired; a single `--pattern`/`--rules` run keeps its existing field set as-is.
+    pub fn parse(&self, data: &str, stats: &mut Stats) -> Result<BTreeMap<String, serde_json::Value>> {
+        stats.bytes_read += data.len() as u64;
+        for rule in &self.rules {
+            if let Some(matches) = rule.pattern.match_against(data) {
                 let mut map = BTreeMap::new();
                 for (k, v) in matches.iter() {
-                    map.insert(k.to_string(), v.to_string());
+                    let value = coerce_field(v, rule.type_hints.get(k).map(String::as_str));
+                    map.insert(k.to_string(), value);
                 }
-                Ok(map)
+                stats.record_match(&rule.name, &map);
+                if self.rules.len() > 1 {
+                    map.entry("_rule".to_string())
+                        .or_insert_with(|| serde_json::Value::from(rule.name.as_str()));
+                }
+                return Ok(map);
             }
         }
+        stats.failed += 1;
+        Err(format!("No matches against data: \"{}\"", data.trim_end()).into())
+    }
+}
+
+/// Reads each `--rules` file as a single named grok pattern: the file stem is the
+/// rule name and its (trimmed) contents are the pattern to compile.
+fn read_rules(rule_files: &[path::PathBuf]) -> Result<Vec<(String, String)>> {
+    let mut rules = Vec::new();
+    for path in rule_files {
+        let name = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.display().to_string());
+        let pattern = fs::read_to_string(path)?.trim().to_string();
+        rules.push((name, pattern));
+    }
+    Ok(rules)
+}
+
+/// Matches a `%{SYNTAX:name:type}` grok token, where `type` is one of our supported coercions.
+fn type_hint_token() -> Regex {
+    Regex::new(r"%\{(?P<syntax>[A-Za-z0-9_]+):(?P<name>[A-Za-z_][A-Za-z0-9_]*):(?P<ty>int|long|float|bool)\}")
+        .expect("static regex is valid")
+}
+
+/// Strips the `:type` suffix off every `%{SYNTAX:name:type}` token, returning the
+/// plain grok pattern alongside the stripped-out `name -> type` mapping.
+fn extract_type_hints(pattern: &str) -> (String, BTreeMap<String, String>) {
+    let token = type_hint_token();
+    let mut hints = BTreeMap::new();
+    let rewritten = token
+        .replace_all(pattern, |caps: &regex::Captures| {
+            hints.insert(caps["name"].to_string(), caps["ty"].to_string());
+            format!("%{{{}:{}}}", &caps["syntax"], &caps["name"])
+        })
+        .to_string();
+    (rewritten, hints)
+}
+
+/// Coerces a captured field's text into the JSON value implied by `type_hint`,
+/// falling back to a plain JSON string when there is no hint or it doesn't parse.
+fn coerce_field(value: &str, type_hint: Option<&str>) -> serde_json::Value {
+    match type_hint {
+        Some("int") | Some("long") => value
+            .parse::<i64>()
+            .map(serde_json::Value::from)
+            .unwrap_or_else(|_| serde_json::Value::from(value)),
+        Some("float") => value
+            .parse::<f64>()
+            .map(serde_json::Value::from)
+            .unwrap_or_else(|_| serde_json::Value::from(value)),
+        Some("bool") => value
+            .parse::<bool>()
+            .map(serde_json::Value::from)
+            .unwrap_or_else(|_| serde_json::Value::from(value)),
+        _ => serde_json::Value::from(value),
     }
 }
 
@@ -212,18 +495,110 @@ fn read_aliases(patterns: &path::Path) -> Result<BTreeMap<String, String>> {
     Ok(aliases)
 }
 
+/// Expands the CLI's raw input paths into an ordered list of regular files: globs
+/// are matched against the filesystem, directories are walked, and the result is
+/// filtered by `include`/`exclude` globs matched against the file name. The list is
+/// returned reversed, so that `Input` popping from its end reads the files in order.
+fn expand_paths(
+    paths: Vec<path::PathBuf>,
+    recursive: bool,
+    include: &[String],
+    exclude: &[String],
+) -> Result<Vec<path::PathBuf>> {
+    let mut expanded = Vec::new();
+    for p in paths {
+        let candidates: Vec<path::PathBuf> = if is_glob(&p) {
+            glob::glob(&p.to_string_lossy())?
+                .filter_map(|r| r.ok())
+                .collect()
+        } else {
+            vec![p]
+        };
+        for c in candidates {
+            if c.is_dir() {
+                if recursive {
+                    walk_dir(&c, &mut expanded)?;
+                } else {
+                    eprintln!(
+                        "Skipping directory {}: pass -R/--recursive to walk it.",
+                        c.display()
+                    );
+                }
+            } else {
+                expanded.push(c);
+            }
+        }
+    }
+    expanded.retain(|p| matches_filters(p, include, exclude));
+    expanded.reverse();
+    Ok(expanded)
+}
+
+fn is_glob(p: &path::Path) -> bool {
+    p.to_string_lossy()
+        .chars()
+        .any(|c| c == '*' || c == '?' || c == '[')
+}
+
+fn walk_dir(dir: &path::Path, out: &mut Vec<path::PathBuf>) -> Result<()> {
+    let mut entries: Vec<path::PathBuf> =
+        fs::read_dir(dir)?.filter_map(|e| e.ok().map(|e| e.path())).collect();
+    entries.sort();
+    for path in entries {
+        if path.is_dir() {
+            walk_dir(&path, out)?;
+        } else if path.is_file() {
+            out.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn matches_filters(path: &path::Path, include: &[String], exclude: &[String]) -> bool {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let included = include.is_empty() || include.iter().any(|pat| glob_match(pat, &name));
+    let excluded = exclude.iter().any(|pat| glob_match(pat, &name));
+    included && !excluded
+}
+
+fn glob_match(pattern: &str, name: &str) -> bool {
+    glob::Pattern::new(pattern)
+        .map(|p| p.matches(name))
+        .unwrap_or(false)
+}
+
 #[derive(Default, Serialize)]
 pub struct Stats {
     pub parsed: u64,
     pub failed: u64,
+    pub bytes_read: u64,
+    pub elapsed_secs: u64,
+    /// Number of records in which each field name was successfully captured.
+    pub field_matches: BTreeMap<String, u64>,
+    /// Number of records each rule fired on, keyed by rule name.
+    pub rule_hits: BTreeMap<String, u64>,
+}
+
+impl Stats {
+    /// Records a successful match: one hit for `rule`, one hit for each matched field.
+    fn record_match(&mut self, rule: &str, fields: &BTreeMap<String, serde_json::Value>) {
+        self.parsed += 1;
+        *self.rule_hits.entry(rule.to_string()).or_insert(0) += 1;
+        for field in fields.keys() {
+            *self.field_matches.entry(field.clone()).or_insert(0) += 1;
+        }
+    }
 }
 
 #[derive(StructOpt, Debug)]
 #[structopt(name = "grok", about = "Parse unstructured data using grok filters.")]
 pub struct Opt {
-    /// Pattern to match
+    /// Pattern to match. Optional when one or more `--rules` are given.
     #[structopt(short, long)]
-    pub pattern: String,
+    pub pattern: Option<String>,
     // File to send output to.
     #[structopt(short, long, parse(from_os_str))]
     pub output: Option<path::PathBuf>,
@@ -245,6 +620,29 @@ pub struct Opt {
     /// Rules field, points to one or more afrs rules.
     #[structopt(short, long, parse(from_os_str))]
     pub rules: Vec<path::PathBuf>,
+    /// Join continuation lines (stack traces, wrapped JSON) onto the previous record.
+    #[structopt(long)]
+    pub multiline: bool,
+    /// Regex marking the start of a new record; non-matching lines are continuations. Only used with `--multiline`.
+    #[structopt(long, parse(try_from_str = parse_regex))]
+    pub line_start: Option<Regex>,
+    /// Recursively walk any input path that is a directory.
+    #[structopt(short = "R", long)]
+    pub recursive: bool,
+    /// Only parse files whose name matches this glob. May be given multiple times.
+    #[structopt(long, parse(try_from_str = parse_glob_filter))]
+    pub include: Vec<String>,
+    /// Skip files whose name matches this glob. May be given multiple times.
+    #[structopt(long, parse(try_from_str = parse_glob_filter))]
+    pub exclude: Vec<String>,
+    /// Keep running after EOF, polling for newly appended lines (like `tail -f`). With
+    /// multiple input paths, only the last file reached is actually tailed; earlier
+    /// files are not revisited once passed.
+    #[structopt(long)]
+    pub follow: bool,
+    /// Emit an incremental `Stats` snapshot every N seconds instead of only at EOF.
+    #[structopt(long)]
+    pub stats_interval: Option<u64>,
 }
 
 #[derive(Debug)]
@@ -255,7 +653,11 @@ pub enum OutputFormat {
     Csv,
 }
 impl OutputFormat {
-    fn handle_parsed(&self, parsed: BTreeMap<String, String>, output: &Output) -> Result<()> {
+    fn handle_parsed(
+        &self,
+        parsed: BTreeMap<String, serde_json::Value>,
+        output: &Output,
+    ) -> Result<()> {
         match self {
             Self::Json => match serde_json::to_string(&parsed) {
                 Ok(j) => output.output(Ok(j)),
@@ -264,28 +666,62 @@ impl OutputFormat {
             Self::Csv => output.output(Ok(
                 parsed
                     .values()
-                    .map(|v| format!("\"{}\"", v))
+                    .map(format_csv_value)
                     .collect::<Vec<String>>()
                     .join(", "), // .to_string()
             )),
         }
     }
-    fn handle_stats(&self, stats: &Stats, output: &Output) -> Result<()> {
+    /// Emits the final, one-time `--stats` report at EOF, on the main output stream
+    /// (stdout, or the `-o` path) exactly as before `--stats-interval` existed.
+    /// `header` controls whether the CSV formatter writes its column header row first.
+    fn handle_stats(&self, stats: &Stats, output: &Output, header: bool) -> Result<()> {
+        self.write_stats(stats, header, |res| output.output(res))
+    }
+    /// Emits a periodic `--stats-interval` snapshot on the dedicated stats stream
+    /// (stderr, or the sibling `.stats` file) so it never interleaves with the
+    /// record stream's own schema. `header` controls whether the CSV formatter
+    /// writes its column header row first.
+    fn handle_stats_interval(&self, stats: &Stats, output: &Output, header: bool) -> Result<()> {
+        self.write_stats(stats, header, |res| output.output_stats(res))
+    }
+    fn write_stats(
+        &self,
+        stats: &Stats,
+        header: bool,
+        write: impl Fn(Result<String>) -> Result<()>,
+    ) -> Result<()> {
         match self {
             Self::Json => match serde_json::to_string(&stats) {
-                Ok(p) => output.output(Ok(p)),
-                Err(e) => output.output(Err(Box::new(e))),
+                Ok(p) => write(Ok(p)),
+                Err(e) => write(Err(Box::new(e))),
             },
             Self::Csv => {
-                output.output(Ok(vec!["parsed", "failed"].join(", ")))?;
-                output.output(Ok(
-                    vec![stats.parsed.to_string(), stats.failed.to_string()].join(", ")
-                ))
+                if header {
+                    write(Ok(
+                        vec!["parsed", "failed", "bytes_read", "elapsed_secs"].join(", "),
+                    ))?;
+                }
+                write(Ok(vec![
+                    stats.parsed.to_string(),
+                    stats.failed.to_string(),
+                    stats.bytes_read.to_string(),
+                    stats.elapsed_secs.to_string(),
+                ]
+                .join(", ")))
             }
         }
         .map(|_| ())
     }
 }
+/// Renders a coerced field value for CSV output: strings are quoted, others bare.
+fn format_csv_value(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(s) => format!("\"{}\"", s),
+        other => other.to_string(),
+    }
+}
+
 fn parse_format(src: &str) -> Result<OutputFormat> {
     match src.to_ascii_lowercase().as_str() {
         "json" => Ok(OutputFormat::Json),
@@ -294,19 +730,115 @@ fn parse_format(src: &str) -> Result<OutputFormat> {
     }
 }
 
+fn parse_regex(src: &str) -> Result<Regex> {
+    Regex::new(src).map_err(|e| e.into())
+}
+
+/// Validates an `--include`/`--exclude` glob up front so a typo'd pattern fails fast
+/// instead of silently matching nothing (`glob_match` treats an invalid pattern as no match).
+fn parse_glob_filter(src: &str) -> Result<String> {
+    glob::Pattern::new(src)?;
+    Ok(src.to_string())
+}
+
+/// Flushes a `--stats-interval` snapshot once due; a no-op when `stats_interval` is `None`.
+fn maybe_emit_stats_interval(
+    stats_interval: Option<u64>,
+    output_format: &OutputFormat,
+    stats: &mut Stats,
+    output: &Output,
+    start: std::time::Instant,
+    last_stats_emit: &mut std::time::Instant,
+    stats_header_written: &mut bool,
+) -> Result<()> {
+    if let Some(interval) = stats_interval {
+        if last_stats_emit.elapsed().as_secs() >= interval {
+            stats.elapsed_secs = start.elapsed().as_secs();
+            output_format.handle_stats_interval(stats, output, !*stats_header_written)?;
+            *stats_header_written = true;
+            *last_stats_emit = std::time::Instant::now();
+        }
+    }
+    Ok(())
+}
+
 fn main() -> Result<()> {
     let opt = Opt::from_args();
+    // Expand directories and globs in the input paths into an ordered file list.
+    let had_input_paths = !opt.input.is_empty();
+    let expanded_input = expand_paths(opt.input, opt.recursive, &opt.include, &opt.exclude)?;
+    // An empty expansion is only a legitimate "read from stdin" signal when the user
+    // never passed any input paths at all. If paths were given but a glob, directory,
+    // or --include/--exclude filter matched nothing, `Input::try_from` would otherwise
+    // fall back to stdin and hang (or read whatever happens to be piped in) instead of
+    // reporting the mismatch.
+    if had_input_paths && expanded_input.is_empty() {
+        return Err("No files matched the given input path(s).".into());
+    }
     // Get a file input handle.
-    let mut input = Input::try_from(opt.input)?;
+    let input = Input::try_from(expanded_input)?;
+    let input = if opt.follow { input.following() } else { input };
+    // Optionally glue continuation lines onto the previous record before matching.
+    let mut input: Box<dyn Iterator<Item = Result<Option<String>>>> = if opt.multiline {
+        Box::new(MultilineJoiner::new(input, opt.line_start))
+    } else {
+        Box::new(input)
+    };
     // Get Grok parser parser. Handle based on options.
-    let grok_parser = GrokParser::new(&opt.pattern, opt.patterns.as_ref(), opt.no_patterns)?;
+    let grok_parser = GrokParser::new(
+        opt.pattern.as_deref(),
+        &opt.rules,
+        opt.patterns.as_ref(),
+        opt.no_patterns,
+    )?;
+    // Multi-rule matching lets different records carry different field sets (e.g. one
+    // rule for nginx, another for syslog), but CSV has a single fixed column header —
+    // a record matched by a different rule than the one that set the header would
+    // silently misalign columns. Require a single rule for CSV output instead.
+    if matches!(opt.output_format, OutputFormat::Csv) && grok_parser.rule_count() > 1 {
+        return Err(
+            "CSV output only supports a single rule (one --pattern or one --rules file); \
+             use --output-format json for multi-rule matching."
+                .into(),
+        );
+    }
     // Get the output struct.
     let output = Output::try_from(opt.output)?;
+    if opt.stats_interval.is_some() {
+        output.ensure_stats_path_available()?;
+    }
     // Generate a stats component.
     let mut stats = Stats::default();
     let mut headers = Vec::new();
+    let start = std::time::Instant::now();
+    let mut last_stats_emit = start;
+    let mut stats_header_written = false;
     //
-    while let Some(Ok(a)) = input.next() {
+    loop {
+        // Check once per iteration whether a `--stats-interval` snapshot is due,
+        // regardless of whether it produces a record, a read error, or a parse failure — a busy
+        // `--follow` stream made up mostly of unmatched lines still gets snapshots.
+        maybe_emit_stats_interval(
+            opt.stats_interval,
+            &opt.output_format,
+            &mut stats,
+            &output,
+            start,
+            &mut last_stats_emit,
+            &mut stats_header_written,
+        )?;
+        let a = match input.next() {
+            // Stream closed: no more input will ever arrive.
+            None => break,
+            // A record failed to read; report it and keep going.
+            Some(Err(e)) => {
+                output.output(Err(e))?;
+                continue;
+            }
+            // Idle tick from `--follow` mode: no record this poll.
+            Some(Ok(None)) => continue,
+            Some(Ok(Some(a))) => a,
+        };
         let parsed = match grok_parser.parse(&a, &mut stats) {
             Ok(p) => p,
             Err(e) => {
@@ -327,8 +859,155 @@ fn main() -> Result<()> {
     }
     //
     if opt.stats {
-        opt.output_format.handle_stats(&stats, &output)?;
+        stats.elapsed_secs = start.elapsed().as_secs();
+        opt.output_format
+            .handle_stats(&stats, &output, true)?;
     }
     //
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn coerce_field_parses_hinted_types() {
+        assert_eq!(coerce_field("4096", Some("int")), serde_json::json!(4096));
+        assert_eq!(coerce_field("4096", Some("long")), serde_json::json!(4096));
+        assert_eq!(coerce_field("3.5", Some("float")), serde_json::json!(3.5));
+        assert_eq!(coerce_field("true", Some("bool")), serde_json::json!(true));
+    }
+
+    #[test]
+    fn coerce_field_falls_back_to_string_without_a_hint() {
+        assert_eq!(
+            coerce_field("hello", None),
+            serde_json::Value::from("hello")
+        );
+    }
+
+    #[test]
+    fn coerce_field_falls_back_to_string_on_parse_failure() {
+        assert_eq!(
+            coerce_field("not-a-number", Some("int")),
+            serde_json::Value::from("not-a-number")
+        );
+    }
+
+    #[test]
+    fn extract_type_hints_strips_the_type_suffix_and_records_it() {
+        let (rewritten, hints) = extract_type_hints("%{IP:client} %{NUMBER:size:int}");
+        assert_eq!(rewritten, "%{IP:client} %{NUMBER:size}");
+        assert_eq!(hints.get("size").map(String::as_str), Some("int"));
+        assert_eq!(hints.get("client"), None);
+    }
+
+    #[test]
+    fn typed_field_round_trips_through_grok_parser() {
+        // Regression test for the assumption the whole feature rests on: that we can
+        // recover `%{NUMBER:x:int}`'s type hint without depending on how the `grok`
+        // crate itself tokenizes (or mangles) a `name:type` capture alias.
+        let parser = GrokParser::new(Some("%{NUMBER:x:int}"), &[], None, false).unwrap();
+        let mut stats = Stats::default();
+        let parsed = parser.parse("4096", &mut stats).unwrap();
+        assert_eq!(parsed.get("x"), Some(&serde_json::json!(4096)));
+    }
+
+    #[test]
+    fn multiline_joiner_joins_without_extra_blank_lines() {
+        let lines: Vec<Result<Option<String>>> = vec![
+            Ok(Some("ERROR boom".to_string())),
+            Ok(Some("  at foo.rs:1".to_string())),
+            Ok(Some("  at bar.rs:2".to_string())),
+            Ok(Some("INFO next record".to_string())),
+        ];
+        let mut joiner = MultilineJoiner::new(lines.into_iter(), None);
+        assert_eq!(
+            joiner.next().unwrap().unwrap().unwrap(),
+            "ERROR boom\n  at foo.rs:1\n  at bar.rs:2"
+        );
+        assert_eq!(
+            joiner.next().unwrap().unwrap().unwrap(),
+            "INFO next record"
+        );
+        assert!(joiner.next().is_none());
+    }
+
+    #[test]
+    fn multiline_joiner_passes_idle_ticks_through_without_losing_the_buffer() {
+        let lines: Vec<Result<Option<String>>> = vec![
+            Ok(Some("ERROR boom".to_string())),
+            Ok(None),
+            Ok(Some("  at foo.rs:1".to_string())),
+        ];
+        let mut joiner = MultilineJoiner::new(lines.into_iter(), None);
+        assert_eq!(joiner.next().unwrap().unwrap(), None);
+        assert_eq!(
+            joiner.next().unwrap().unwrap().unwrap(),
+            "ERROR boom\n  at foo.rs:1"
+        );
+    }
+
+    #[test]
+    fn is_continuation_defaults_to_leading_whitespace() {
+        let joiner = MultilineJoiner::new(std::iter::empty::<Result<Option<String>>>(), None);
+        assert!(joiner.is_continuation("  indented"));
+        assert!(joiner.is_continuation("\ttabbed"));
+        assert!(!joiner.is_continuation("not indented"));
+    }
+
+    #[test]
+    fn is_glob_detects_wildcard_characters() {
+        assert!(is_glob(path::Path::new("logs/*.log")));
+        assert!(is_glob(path::Path::new("logs/app-?.log")));
+        assert!(is_glob(path::Path::new("logs/[ab].log")));
+        assert!(!is_glob(path::Path::new("logs/app.log")));
+    }
+
+    #[test]
+    fn matches_filters_applies_include_and_exclude_globs() {
+        let path = path::Path::new("logs/app.log");
+        assert!(matches_filters(path, &[], &[]));
+        assert!(matches_filters(path, &["*.log".to_string()], &[]));
+        assert!(!matches_filters(path, &["*.txt".to_string()], &[]));
+        assert!(!matches_filters(path, &[], &["app.*".to_string()]));
+    }
+
+    #[test]
+    fn parse_glob_filter_rejects_an_invalid_pattern() {
+        assert!(parse_glob_filter("*.log").is_ok());
+        assert!(parse_glob_filter("[invalid").is_err());
+    }
+
+    #[test]
+    fn expand_paths_skips_directories_without_recursive() {
+        let dir = std::env::temp_dir().join(format!(
+            "grok-cli-test-{}-{}",
+            std::process::id(),
+            "expand_paths_skips_directories_without_recursive"
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("a.log"), "hello").unwrap();
+        //
+        let found = expand_paths(vec![dir.clone()], false, &[], &[]).unwrap();
+        assert!(found.is_empty());
+        //
+        let found = expand_paths(vec![dir.clone()], true, &[], &[]).unwrap();
+        assert_eq!(found, vec![dir.join("a.log")]);
+        //
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn stats_record_match_tracks_rule_and_field_hits() {
+        let mut stats = Stats::default();
+        let mut fields = BTreeMap::new();
+        fields.insert("client".to_string(), serde_json::json!("1.2.3.4"));
+        stats.record_match("nginx", &fields);
+        stats.record_match("nginx", &fields);
+        assert_eq!(stats.parsed, 2);
+        assert_eq!(stats.rule_hits.get("nginx"), Some(&2));
+        assert_eq!(stats.field_matches.get("client"), Some(&2));
+    }
+}